@@ -0,0 +1,184 @@
+//! Composite "clump" particles: rigid assemblies of several overlapping spheres,
+//! packed and oriented as a single unit. This lets the library generate specimens
+//! with a predefined non-spherical shape or aspect ratio, the way DEM codes do,
+//! while reusing all of the sphere-based overlap and containment math.
+
+use nalgebra::{Point3, Rotation3, Unit, Vector3};
+use rand::{self, Rng};
+use shapes::Sphere;
+use Container;
+
+/// A rigid assembly of spheres with fixed offsets from a reference centre, packed and
+/// oriented as a single unit.
+///
+/// `offsets` describes each constituent sphere as a `(offset, radius)` pair relative
+/// to `center`, before `orientation` is applied; rotating `orientation` and
+/// translating `center` therefore moves the whole clump rigidly.
+#[derive(Debug, Clone)]
+pub struct Clump {
+    /// Reference centre of the clump.
+    pub center: Point3<f32>,
+    /// Orientation applied to every constituent offset before translating by `center`.
+    pub orientation: Rotation3<f32>,
+    offsets: Vec<(Vector3<f32>, f32)>,
+}
+
+impl Clump {
+    /// Creates a new clump centred at `center` with identity orientation, from a set
+    /// of `(offset, radius)` pairs describing each constituent sphere relative to that
+    /// centre.
+    pub fn new(center: Point3<f32>, offsets: Vec<(Vector3<f32>, f32)>) -> Clump {
+        Clump {
+            center: center,
+            orientation: Rotation3::identity(),
+            offsets: offsets,
+        }
+    }
+
+    /// The radius of the smallest sphere, centred on `center`, that fully encloses
+    /// this clump regardless of `orientation`. Used as a stand-in particle size when
+    /// searching for candidate positions with the sphere advancing front.
+    pub fn bounding_radius(&self) -> f32 {
+        self.offsets
+            .iter()
+            .map(|&(offset, radius)| ::nalgebra::norm(&offset) + radius)
+            .fold(0., f32::max)
+    }
+
+    /// The constituent spheres of this clump in world space: `orientation` applied to
+    /// each offset, then translated by `center`.
+    pub fn spheres(&self) -> Vec<Sphere> {
+        self.offsets
+            .iter()
+            .map(|&(offset, radius)| Sphere::new(self.center + self.orientation * offset, radius))
+            .collect()
+    }
+
+    /// Total volume of this clump, accounting for intra-clump overlaps between
+    /// constituent spheres so fused grains are not double counted.
+    ///
+    /// Two spheres can be corrected for exactly with a pairwise lens subtraction, but
+    /// that formula under-counts once three or more spheres mutually overlap (it never
+    /// adds back the triple-intersection term), so clumps of three or more spheres
+    /// fall back to a Monte-Carlo estimate of the union volume instead, drawn from
+    /// `rand::thread_rng()`. See [volume_with_rng](#method.volume_with_rng) for a
+    /// reproducible variant.
+    pub fn volume(&self) -> f32 {
+        self.volume_with_rng(&mut rand::thread_rng())
+    }
+
+    /// As [volume](#method.volume), but threading a caller-supplied `rng` through the
+    /// Monte-Carlo estimate used for clumps of three or more spheres, instead of
+    /// reaching for `rand::thread_rng()` internally, so the result is reproducible for
+    /// a fixed seed (matching the `_with_rng`/`_seeded` convention used for packing).
+    pub fn volume_with_rng<R: Rng>(&self, rng: &mut R) -> f32 {
+        let spheres = self.spheres();
+        match spheres.len() {
+            0 => 0.,
+            1 => spheres[0].volume(),
+            2 => spheres[0].volume() + spheres[1].volume() - lens_volume(&spheres[0], &spheres[1]),
+            _ => monte_carlo_union_volume(&spheres, rng),
+        }
+    }
+
+    /// True if every constituent sphere of this clump (at its current `center` and
+    /// `orientation`) fits inside `container`.
+    pub fn contains_in<C: Container>(&self, container: &C) -> bool {
+        self.spheres().iter().all(|sphere| container.contains(sphere))
+    }
+
+    /// True if any constituent sphere of this clump overlaps any sphere in `others`.
+    pub fn overlaps_any(&self, others: &[Sphere]) -> bool {
+        let spheres = self.spheres();
+        spheres.iter().any(|sphere| {
+            others.iter().any(|other| sphere.overlaps(other))
+        })
+    }
+
+    /// Returns a copy of this clump re-centred at `center` with `orientation` applied.
+    pub fn placed_at(&self, center: Point3<f32>, orientation: Rotation3<f32>) -> Clump {
+        Clump {
+            center: center,
+            orientation: orientation,
+            offsets: self.offsets.clone(),
+        }
+    }
+}
+
+/// Volume of the lens-shaped intersection of two overlapping spheres, used to avoid
+/// double-counting the volume where a clump's constituent spheres fuse together.
+fn lens_volume(a: &Sphere, b: &Sphere) -> f32 {
+    let d = ::nalgebra::distance(&a.center, &b.center);
+    if d >= a.radius + b.radius {
+        return 0.;
+    }
+    if d <= (a.radius - b.radius).abs() {
+        // One sphere is entirely inside the other; the lens is just the smaller sphere.
+        let r_min = a.radius.min(b.radius);
+        return 4. / 3. * ::std::f32::consts::PI * r_min.powi(3);
+    }
+    // Standard two-sphere lens volume, e.g. Weisstein, "Sphere-Sphere Intersection".
+    let pi = ::std::f32::consts::PI;
+    pi * (a.radius + b.radius - d).powi(2) *
+        (d.powi(2) + 2. * d * (a.radius + b.radius) - 3. * (a.radius - b.radius).powi(2)) /
+        (12. * d)
+}
+
+/// Monte-Carlo estimate of the union volume of `spheres`: samples points uniformly in
+/// the axis-aligned bounding box of the whole set and scales the bounding box volume
+/// by the fraction that land inside at least one sphere.
+fn monte_carlo_union_volume<R: Rng>(spheres: &[Sphere], rng: &mut R) -> f32 {
+    const SAMPLES: usize = 20_000;
+
+    let inf = ::std::f32::INFINITY;
+    let min_corner = spheres.iter().fold(
+        Point3::new(inf, inf, inf),
+        |acc, sphere| {
+            Point3::new(
+                acc.x.min(sphere.center.x - sphere.radius),
+                acc.y.min(sphere.center.y - sphere.radius),
+                acc.z.min(sphere.center.z - sphere.radius),
+            )
+        },
+    );
+    let max_corner = spheres.iter().fold(
+        Point3::new(-inf, -inf, -inf),
+        |acc, sphere| {
+            Point3::new(
+                acc.x.max(sphere.center.x + sphere.radius),
+                acc.y.max(sphere.center.y + sphere.radius),
+                acc.z.max(sphere.center.z + sphere.radius),
+            )
+        },
+    );
+    let box_volume = (max_corner.x - min_corner.x) * (max_corner.y - min_corner.y) *
+        (max_corner.z - min_corner.z);
+
+    let inside = (0..SAMPLES)
+        .filter(|_| {
+            let point = Point3::new(
+                rng.gen_range(min_corner.x, max_corner.x),
+                rng.gen_range(min_corner.y, max_corner.y),
+                rng.gen_range(min_corner.z, max_corner.z),
+            );
+            spheres.iter().any(|sphere| {
+                ::nalgebra::distance(&sphere.center, &point) <= sphere.radius
+            })
+        })
+        .count();
+
+    box_volume * inside as f32 / SAMPLES as f32
+}
+
+/// A random unit vector, uniformly distributed over the sphere, used to pick a random
+/// rotation axis when trying clump orientations.
+pub(crate) fn random_unit_vector<R: Rng>(rng: &mut R) -> Unit<Vector3<f32>> {
+    let costheta: f32 = rng.gen_range(-1., 1.);
+    let theta = costheta.acos();
+    let phi: f32 = rng.gen_range(0., 2. * ::std::f32::consts::PI);
+    Unit::new_normalize(Vector3::new(
+        theta.sin() * phi.cos(),
+        theta.sin() * phi.sin(),
+        theta.cos(),
+    ))
+}
@@ -0,0 +1,145 @@
+//! A uniform spatial-hash grid used internally to accelerate neighbour queries
+//! over the set of already-placed spheres.
+//!
+//! The packing algorithm (and `PackedVolume`'s post-hoc analysis methods) repeatedly
+//! ask "which spheres lie within some small distance of this point?". Answering that
+//! by scanning every sphere is O(N) per query and O(N²) over a full packing. Instead
+//! we bucket sphere centres into cubic cells sized so that any sphere able to interact
+//! with a query point must lie in the query's cell or one of its 26 neighbours.
+
+use nalgebra::Point3;
+use std::collections::HashMap;
+
+/// A cell-partitioned index over sphere centres, keyed by integer cell coordinates.
+///
+/// Cells are cubic with edge length `cell_size`, chosen by the caller to be at least
+/// as large as the greatest interaction distance expected between a query point and
+/// any candidate sphere (for packing, `2*max_radius + 2*new_radius` over the spheres
+/// encountered so far).
+///
+/// When built with a `period` (see [new_periodic](#method.new_periodic)), the requested
+/// `cell_size` is rounded *up* to the nearest size that divides `period` evenly (so
+/// `cells_per_axis` cells of equal size tile the box exactly, with no undersized seam
+/// cell), except that at least 3 cells always span the period, and cell
+/// coordinates are wrapped modulo `cells_per_axis` along each axis. That way a point
+/// near one face of a periodic box and its neighbours near the opposite face always
+/// land in cells that are genuinely adjacent under the wrap, just as
+/// [Container::distance](../trait.Container.html#method.distance)'s minimum-image
+/// convention considers them close.
+#[derive(Debug, Clone)]
+pub(crate) struct SpatialGrid {
+    cell_size: f32,
+    /// The edge length of the periodic box this grid wraps around, if any.
+    period: Option<f32>,
+    /// Number of cells spanning one period along an axis. Unused (left at 0) for a
+    /// non-periodic grid.
+    cells_per_axis: i64,
+    cells: HashMap<(i64, i64, i64), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    /// Creates an empty, non-periodic grid with the given `cell_size`.
+    pub(crate) fn new(cell_size: f32) -> SpatialGrid {
+        SpatialGrid {
+            cell_size: cell_size.max(::std::f32::EPSILON),
+            period: None,
+            cells_per_axis: 0,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Creates an empty grid whose cell coordinates wrap modulo `period` along every
+    /// axis (see the type-level docs). At least 3 cells always span the period, so the
+    /// 3x3x3 neighbour stencil used by [neighbours](#method.neighbours) always reaches
+    /// every cell adjacent to a seam. `cells_per_axis` is `period / cell_size` rounded
+    /// down, so the actual size, `period / cells_per_axis`, is `cell_size` rounded up to
+    /// tile the box exactly; only when fewer than 3 such cells would fit is it smaller
+    /// than `cell_size`, and the 3x3x3 stencil then covers the whole box.
+    pub(crate) fn new_periodic(cell_size: f32, period: f32) -> SpatialGrid {
+        let period = period.max(::std::f32::EPSILON);
+        let requested = cell_size.max(::std::f32::EPSILON);
+        let cells_per_axis = ((period / requested).floor() as i64).max(3);
+        SpatialGrid {
+            cell_size: period / cells_per_axis as f32,
+            period: Some(period),
+            cells_per_axis: cells_per_axis,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Builds a non-periodic grid from scratch over `points`, sizing cells from
+    /// `cell_size`.
+    pub(crate) fn build(points: &[Point3<f32>], cell_size: f32) -> SpatialGrid {
+        let mut grid = SpatialGrid::new(cell_size);
+        for (idx, point) in points.iter().enumerate() {
+            grid.insert(idx, point);
+        }
+        grid
+    }
+
+    /// As [build](#method.build), but wrapping cell coordinates modulo `period` (see
+    /// [new_periodic](#method.new_periodic)).
+    pub(crate) fn build_periodic(points: &[Point3<f32>], cell_size: f32, period: f32) -> SpatialGrid {
+        let mut grid = SpatialGrid::new_periodic(cell_size, period);
+        for (idx, point) in points.iter().enumerate() {
+            grid.insert(idx, point);
+        }
+        grid
+    }
+
+    /// Wraps a cell coordinate `c` into `[0, cells_per_axis)` for a periodic grid;
+    /// returns `c` unchanged for a non-periodic one.
+    fn wrap(&self, c: i64) -> i64 {
+        match self.period {
+            Some(_) => {
+                let n = self.cells_per_axis;
+                ((c % n) + n) % n
+            }
+            None => c,
+        }
+    }
+
+    /// The (possibly wrapped) cell coordinate a given `point` falls into.
+    fn cell_of(&self, point: &Point3<f32>) -> (i64, i64, i64) {
+        (
+            self.wrap((point.x / self.cell_size).floor() as i64),
+            self.wrap((point.y / self.cell_size).floor() as i64),
+            self.wrap((point.z / self.cell_size).floor() as i64),
+        )
+    }
+
+    /// Registers the sphere at index `idx`, centred at `point`, with this grid.
+    pub(crate) fn insert(&mut self, idx: usize, point: &Point3<f32>) {
+        self.cells.entry(self.cell_of(point)).or_insert_with(Vec::new).push(idx);
+    }
+
+    /// Returns the indices of every sphere registered in `point`'s cell or one of its
+    /// 26 neighbouring cells (wrapped around a periodic box, if this grid is
+    /// periodic). Callers must still verify the true distance, since the grid only
+    /// narrows the candidate set.
+    pub(crate) fn neighbours(&self, point: &Point3<f32>) -> Vec<usize> {
+        let (cx, cy, cz) = self.cell_of(point);
+        let mut candidates = Vec::new();
+        for dx in -1..2 {
+            for dy in -1..2 {
+                for dz in -1..2 {
+                    let key = (self.wrap(cx + dx), self.wrap(cy + dy), self.wrap(cz + dz));
+                    if let Some(bucket) = self.cells.get(&key) {
+                        candidates.extend_from_slice(bucket);
+                    }
+                }
+            }
+        }
+        // `new_periodic` keeps at least 3 cells per axis, so the wrapped 3x3x3 search
+        // above visits each cell once; deduplicating is a safeguard so a sphere is
+        // never reported (and so counted as a contact) more than once.
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+
+    /// The edge length of a single cubic cell.
+    pub(crate) fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+}
@@ -57,18 +57,26 @@ extern crate nalgebra;
 extern crate rand;
 #[cfg(feature = "serde-1")]
 extern crate serde;
+#[cfg(feature = "libm")]
+extern crate libm;
 
 pub mod shapes;
 pub mod util;
+pub mod periodic;
+pub mod clump;
 #[cfg(feature = "serde-1")]
 mod serialization;
+mod grid;
+mod ops;
 
-use nalgebra::Point3;
-use nalgebra::core::{Matrix, Matrix3};
+use nalgebra::{Point3, Rotation3};
+use nalgebra::core::{Matrix, Matrix3, Vector3};
 use rand::Rng;
-use rand::distributions::IndependentSample;
+use rand::distributions::{IndependentSample, Sample};
 use std::iter::repeat;
 use shapes::Sphere;
+use grid::SpatialGrid;
+use clump::{Clump, random_unit_vector};
 
 /// The `Container` trait must be implemented for all shapes you wish to pack spheres into.
 /// Standard shapes such as spheres and cuboids already derrive this trait. More complicated
@@ -81,8 +89,31 @@ pub trait Container {
     fn contains(&self, sphere: &Sphere) -> bool;
     /// Calculates the volume of this container in normalised units.
     fn volume(&self) -> f32;
+    /// Calculates the distance between two points as seen by this container's geometry.
+    /// Defaults to ordinary Euclidean distance. Containers with periodic boundaries
+    /// (see [periodic](periodic/index.html)) override this with the minimum-image
+    /// convention, so a sphere near one face is correctly seen as close to spheres
+    /// near the opposite face.
+    fn distance(&self, a: &Point3<f32>, b: &Point3<f32>) -> f32 {
+        nalgebra::distance(a, b)
+    }
+    /// The edge length of this container's periodic box, if it has one. Defaults to
+    /// `None`. Containers with periodic boundaries (see [periodic](periodic/index.html))
+    /// override this so that [SpatialGrid](grid/struct.SpatialGrid.html)s built against
+    /// them can wrap cell lookups the same way [distance](#method.distance) wraps its
+    /// minimum-image convention, rather than missing spheres near the opposite face.
+    fn period(&self) -> Option<f32> {
+        None
+    }
 }
 
+/// Default tolerance used when testing whether two spheres are in contact: the
+/// absolute difference between their centre distance and the sum of their radii must
+/// fall below this value. Methods that decide this for you (`coordination_number`,
+/// `fabric_tensor`) use this constant; the `_with_tolerance` variants let callers pick
+/// a looser or tighter criterion, e.g. when working with noisy input data.
+pub const DEFAULT_CONTACT_TOLERANCE: f32 = 0.001;
+
 /// To obtain quantitative values of your packing effectiveness, `PackedVolume` provides
 /// a number of useful indicators of such.
 #[derive(Debug)]
@@ -115,6 +146,21 @@ impl<C: Container> PackedVolume<C> {
         }
     }
 
+    /// As [new](#method.new), but packs via [pack_spheres_seeded](fn.pack_spheres_seeded.html)
+    /// using the caller-supplied `rng` instead of `rand::thread_rng()`. Pass a seeded
+    /// `rng` (e.g. `rand::StdRng::from_seed`) to obtain a bit-reproducible packing,
+    /// needed for regression tests and cross-platform DEM initial conditions.
+    pub fn with_rng<R: IndependentSample<f64>, Rg: Rng>(
+        container: C,
+        mut size_distribution: &mut R,
+        rng: &mut Rg,
+    ) -> PackedVolume<C> {
+        PackedVolume::<C> {
+            spheres: pack_spheres_seeded::<C, R, Rg>(&container, &mut size_distribution, rng),
+            container: container,
+        }
+    }
+
     /// Calculates the volume fraction ν = Vs/V: the volume of all spheres packed into a container
     /// divided by the volume of said container.
     ///
@@ -135,6 +181,100 @@ impl<C: Container> PackedVolume<C> {
         (vol_total - vol_spheres) / vol_spheres
     }
 
+    /// Computes the radial distribution function g(r), the standard structural
+    /// fingerprint of a packing: a first peak at contact, and for dense random
+    /// packings a characteristic split second peak.
+    ///
+    /// Pairwise centre distances are histogrammed into `bins` shells of width
+    /// `r_max / bins` out to `r_max`, then each shell's count is normalised by the
+    /// ideal-gas expectation `N · ρ · V_shell`, where `ρ = N / container.volume()`.
+    /// Near the container wall a shell around a particle is only partially inside the
+    /// container, so the naive `4/3 π (r_outer³ - r_inner³)` shell volume would
+    /// over-count there; instead we estimate the true available shell volume per
+    /// particle by Monte-Carlo sampling points in the shell and testing
+    /// `container.contains`, averaged over all particle positions.
+    ///
+    /// Returns one `(bin centre, g value)` pair per bin.
+    pub fn radial_distribution(&self, bins: usize, r_max: f32) -> Vec<(f32, f32)> {
+        let mut rng = rand::thread_rng();
+        self.radial_distribution_with_rng(bins, r_max, &mut rng)
+    }
+
+    /// As [radial_distribution](#method.radial_distribution), but threading a
+    /// caller-supplied `rng` through the Monte-Carlo boundary correction instead of
+    /// reaching for `rand::thread_rng()` internally, so results are reproducible for a
+    /// fixed seed (matching [pack_spheres_seeded](fn.pack_spheres_seeded.html)).
+    pub fn radial_distribution_with_rng<Rg: Rng>(
+        &self,
+        bins: usize,
+        r_max: f32,
+        rng: &mut Rg,
+    ) -> Vec<(f32, f32)> {
+        let num_particles = self.spheres.len();
+        if num_particles == 0 || bins == 0 || r_max <= 0. {
+            return Vec::new();
+        }
+
+        let delta_r = r_max / bins as f32;
+        let density = num_particles as f32 / self.container.volume();
+
+        // Reuse the neighbour grid from the packing algorithm to avoid an O(N²) scan:
+        // a cell size of r_max guarantees every pair within r_max shares a cell or
+        // lies in a neighbouring one. build_grid wraps this around the container's
+        // periodic box (if any), so pairs spanning a PeriodicCuboid face are found
+        // too, matching container.distance's minimum-image convention below.
+        let grid = build_grid(
+            &self.container,
+            &self.spheres.iter().map(|sphere| sphere.center).collect::<Vec<_>>(),
+            r_max,
+        );
+        let mut counts = vec![0usize; bins];
+        for (i, sphere) in self.spheres.iter().enumerate() {
+            for j in grid.neighbours(&sphere.center) {
+                if j == i {
+                    continue;
+                }
+                let d = self.container.distance(&sphere.center, &self.spheres[j].center);
+                if d < r_max {
+                    let bin = (d / delta_r) as usize;
+                    if bin < bins {
+                        counts[bin] += 1;
+                    }
+                }
+            }
+        }
+
+        const MC_SAMPLES: usize = 200;
+        let mut result = Vec::with_capacity(bins);
+        for (bin, &count) in counts.iter().enumerate() {
+            let r_inner = bin as f32 * delta_r;
+            let r_outer = r_inner + delta_r;
+            let r_mid = 0.5 * (r_inner + r_outer);
+
+            // Average, over every particle, the fraction of the shell [r_inner, r_outer)
+            // around it that actually lies inside the container.
+            let mut available_fraction_sum = 0.;
+            for sphere in &self.spheres {
+                let mut inside = 0;
+                for _ in 0..MC_SAMPLES {
+                    let sample = random_point_in_shell(&sphere.center, r_inner, r_outer, rng);
+                    if self.container.contains(&Sphere::new(sample, 0.)) {
+                        inside += 1;
+                    }
+                }
+                available_fraction_sum += inside as f32 / MC_SAMPLES as f32;
+            }
+            let available_fraction = available_fraction_sum / num_particles as f32;
+            let shell_volume = (4. / 3.) * ::std::f32::consts::PI *
+                (r_outer.powi(3) - r_inner.powi(3)) * available_fraction;
+
+            let ideal = num_particles as f32 * density * shell_volume;
+            let g = if ideal > 0. { count as f32 / ideal } else { 0. };
+            result.push((r_mid, g));
+        }
+        result
+    }
+
     /// The coordination number indicates the connectivity of the packing.
     /// For any given sphere in the packing, its coordination number is defined as
     /// the number of spheres it is in contact with. This function returns the
@@ -142,9 +282,10 @@ impl<C: Container> PackedVolume<C> {
     /// overall coordination number of the system.
     pub fn coordination_number(&self) -> f32 {
         let num_particles = self.spheres.len() as f32;
+        let grid = self.neighbour_grid(DEFAULT_CONTACT_TOLERANCE);
         let mut coordinations = 0;
         for idx in 0..self.spheres.len() {
-            coordinations += self.sphere_contacts_count(idx);
+            coordinations += self.contact_indices(idx, &grid, DEFAULT_CONTACT_TOLERANCE).len();
         }
         coordinations as f32 / num_particles
     }
@@ -153,17 +294,18 @@ impl<C: Container> PackedVolume<C> {
     /// Perfectly isotropic packing should see the diagonals of this matrix = 1/3. Deviations from this value
     /// indicates the amount of anisotropy in the system.
     pub fn fabric_tensor(&self) -> Matrix3<f32> {
+        let grid = self.neighbour_grid(DEFAULT_CONTACT_TOLERANCE);
         let phi = |i: usize, j: usize| {
             let mut sum_all = 0.;
             for idx in 0..self.spheres.len() {
                 let center = self.spheres[idx].center.coords;
                 // The set of all spheres in contact with the current sphere
-                let p_c = self.sphere_contacts(idx);
+                let p_c = self.contact_indices(idx, &grid, DEFAULT_CONTACT_TOLERANCE);
                 // Number of spheres in contact with the current sphere
                 let m_p = p_c.len() as f32;
                 let mut sum_vec = 0.;
-                for c in p_c.iter() {
-                    let vec_n_pc = Matrix::cross(&center, &c.center.coords);
+                for &c in p_c.iter() {
+                    let vec_n_pc = Matrix::cross(&center, &self.spheres[c].center.coords);
                     // The unit vector pointing from the center of the current sphere to
                     // the center of a connecting sphere
                     let n_pc = vec_n_pc / nalgebra::norm(&vec_n_pc);
@@ -177,31 +319,274 @@ impl<C: Container> PackedVolume<C> {
         Matrix3::from_fn(|r, c| phi(r, c))
     }
 
-    /// Returns a set of spheres connected to the sphere at a chosen index.
-    fn sphere_contacts(&self, sphere_idx: usize) -> Vec<Sphere> {
+    /// Builds a spatial grid over every sphere centre, with cells sized so that any
+    /// two spheres in contact under the contact tolerance `tol` are guaranteed to fall
+    /// in the same or a neighbouring cell. Used to avoid an O(N²) scan in the
+    /// contact-counting helpers below.
+    fn neighbour_grid(&self, tol: f32) -> SpatialGrid {
+        let max_radius = self.spheres.iter().map(|sphere| sphere.radius).fold(
+            0.,
+            f32::max,
+        );
+        let cell_size = 2. * max_radius + 2. * tol;
+        let centers: Vec<Point3<f32>> = self.spheres.iter().map(|sphere| sphere.center).collect();
+        build_grid(&self.container, &centers, cell_size)
+    }
+
+    /// Returns the indices of spheres connected to the sphere at `sphere_idx`, using a
+    /// precomputed neighbour `grid` to only examine nearby candidates and `tol` as the
+    /// contact tolerance (see [DEFAULT_CONTACT_TOLERANCE](constant.DEFAULT_CONTACT_TOLERANCE.html)).
+    fn contact_indices(&self, sphere_idx: usize, grid: &SpatialGrid, tol: f32) -> Vec<usize> {
         let center = self.spheres[sphere_idx].center;
         let radius = self.spheres[sphere_idx].radius;
-        self.spheres
-            .iter()
-            .cloned()
-            .filter(|sphere| {
-                (nalgebra::distance(&center, &sphere.center) - (radius + sphere.radius)).abs() <
-                    0.001
+        grid.neighbours(&center)
+            .into_iter()
+            .filter(|&idx| {
+                idx != sphere_idx &&
+                    (self.container.distance(&center, &self.spheres[idx].center) -
+                         (radius + self.spheres[idx].radius))
+                        .abs() < tol
             })
             .collect()
     }
 
-    /// Calculates the number of contacts a sphere has with the rest of the packed set.
-    fn sphere_contacts_count(&self, sphere_idx: usize) -> usize {
-        let center = self.spheres[sphere_idx].center;
-        let radius = self.spheres[sphere_idx].radius;
+    /// Total number of contacts in the packing, N_c: each touching pair of spheres is
+    /// counted once.
+    pub fn contact_count(&self) -> usize {
+        self.contact_count_with_tolerance(DEFAULT_CONTACT_TOLERANCE)
+    }
+
+    /// As [contact_count](#method.contact_count), with an explicit contact `tol`erance.
+    pub fn contact_count_with_tolerance(&self, tol: f32) -> usize {
+        let grid = self.neighbour_grid(tol);
+        (0..self.spheres.len())
+            .map(|idx| {
+                self.contact_indices(idx, &grid, tol)
+                    .iter()
+                    .filter(|&&neighbour| neighbour > idx)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Iteratively strips "rattlers" from the contact network: spheres with fewer than
+    /// 4 contacts, the minimum required for force balance and local rigidity in 3D.
+    /// Removing a rattler can drop its neighbours below the threshold in turn, so this
+    /// repeats until a fixed point. Returns the indices of every sphere classified as a
+    /// rattler, i.e. not part of the mechanical [backbone](#method.backbone).
+    pub fn rattlers(&self) -> Vec<usize> {
+        self.rattlers_with_tolerance(DEFAULT_CONTACT_TOLERANCE)
+    }
+
+    /// As [rattlers](#method.rattlers), with an explicit contact `tol`erance.
+    pub fn rattlers_with_tolerance(&self, tol: f32) -> Vec<usize> {
+        let grid = self.neighbour_grid(tol);
+        let adjacency: Vec<Vec<usize>> = (0..self.spheres.len())
+            .map(|idx| self.contact_indices(idx, &grid, tol))
+            .collect();
+        let mut alive = vec![true; self.spheres.len()];
+
+        loop {
+            let mut removed_any = false;
+            for idx in 0..self.spheres.len() {
+                if alive[idx] && adjacency[idx].iter().filter(|&&n| alive[n]).count() < 4 {
+                    alive[idx] = false;
+                    removed_any = true;
+                }
+            }
+            if !removed_any {
+                break;
+            }
+        }
+
+        (0..self.spheres.len()).filter(|&idx| !alive[idx]).collect()
+    }
+
+    /// The surviving, load-bearing set of spheres once every rattler has been removed:
+    /// the mechanical backbone of the packing.
+    pub fn backbone(&self) -> Vec<usize> {
+        self.backbone_with_tolerance(DEFAULT_CONTACT_TOLERANCE)
+    }
+
+    /// As [backbone](#method.backbone), with an explicit contact `tol`erance.
+    pub fn backbone_with_tolerance(&self, tol: f32) -> Vec<usize> {
+        let rattlers = self.rattlers_with_tolerance(tol);
+        (0..self.spheres.len())
+            .filter(|idx| !rattlers.contains(idx))
+            .collect()
+    }
+
+    /// The mean coordination number of the mechanical backbone only. Unlike
+    /// `coordination_number`, which counts contacts to rattlers and so understates
+    /// connectivity, this restricts the average to spheres that survive rattler
+    /// removal.
+    pub fn backbone_coordination_number(&self) -> f32 {
+        self.backbone_coordination_number_with_tolerance(DEFAULT_CONTACT_TOLERANCE)
+    }
+
+    /// As [backbone_coordination_number](#method.backbone_coordination_number), with an
+    /// explicit contact `tol`erance.
+    pub fn backbone_coordination_number_with_tolerance(&self, tol: f32) -> f32 {
+        let grid = self.neighbour_grid(tol);
+        let backbone = self.backbone_with_tolerance(tol);
+        if backbone.is_empty() {
+            return 0.;
+        }
+        let in_backbone: Vec<bool> = (0..self.spheres.len())
+            .map(|idx| backbone.contains(&idx))
+            .collect();
+        let total: usize = backbone
+            .iter()
+            .map(|&idx| {
+                self.contact_indices(idx, &grid, tol)
+                    .iter()
+                    .filter(|&&n| in_backbone[n])
+                    .count()
+            })
+            .sum();
+        total as f32 / backbone.len() as f32
+    }
+
+    /// A constructive densification pass run after the advancing front terminates, to
+    /// close up the residual voids the sequential front inevitably leaves without
+    /// resorting to an expensive gravity/DEM relaxation.
+    ///
+    /// Each sweep visits every sphere smallest-gap-first (the gap to its nearest
+    /// neighbour, found by scanning every other sphere; the container wall does not
+    /// enter this ordering), grows its radius up to the point where it just touches
+    /// its nearest neighbour or the container wall, then
+    /// attempts a small translation toward the centroid of its contacts, keeping it
+    /// inside `container` and non-overlapping throughout. Repeats for `iterations`
+    /// sweeps or until the incremental gain in `volume_fraction` falls below `1e-4`.
+    ///
+    /// Returns the `(before, after)` volume fraction.
+    pub fn densify(&mut self, iterations: usize) -> (f32, f32) {
+        let before = self.volume_fraction();
+        let mut fraction = before;
+
+        for _ in 0..iterations {
+            let mut gaps: Vec<(usize, f32)> = (0..self.spheres.len())
+                .map(|idx| (idx, self.nearest_gap(idx)))
+                .collect();
+            gaps.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+            // grow_sphere scans every other sphere directly and nudge_toward_contacts
+            // rebuilds its neighbour grid on every call (see their doc comments), so
+            // they always see the current radii and positions, including those just
+            // changed earlier in this same sweep.
+            for (idx, _) in gaps {
+                self.grow_sphere(idx);
+                self.nudge_toward_contacts(idx);
+            }
+
+            let new_fraction = self.volume_fraction();
+            let gain = new_fraction - fraction;
+            fraction = new_fraction;
+            if gain < 1e-4 {
+                break;
+            }
+        }
+
+        (before, fraction)
+    }
+
+    /// The smallest gap between the sphere at `idx` and its nearest neighbour, used to
+    /// decide which sphere `densify` should grow first.
+    ///
+    /// Scans every other sphere directly rather than through a neighbour grid: the gap
+    /// `densify` cares about can be far larger than a contact-sized grid cell (a sphere
+    /// sitting in a large void has no neighbours nearby at all), so a grid sized for
+    /// contact queries would silently miss the very neighbour that should constrain it.
+    fn nearest_gap(&self, idx: usize) -> f32 {
+        let center = self.spheres[idx].center;
+        let radius = self.spheres[idx].radius;
         self.spheres
             .iter()
-            .filter(|sphere| {
-                (nalgebra::distance(&center, &sphere.center) - (radius + sphere.radius)).abs() <
-                    0.001
+            .enumerate()
+            .filter(|&(other, _)| other != idx)
+            .map(|(_, sphere)| {
+                self.container.distance(&center, &sphere.center) - radius - sphere.radius
             })
-            .count()
+            .fold(::std::f32::INFINITY, f32::min)
+    }
+
+    /// Grows the sphere at `idx`'s radius as far as possible without overlapping any
+    /// other sphere or leaving the container. Uses a binary search on the radius since
+    /// `contains` may reject arbitrarily large spheres near a curved wall even when the
+    /// neighbour gap alone would allow further growth.
+    ///
+    /// Scans every other sphere directly for the same reason as `nearest_gap`: a
+    /// contact-sized neighbour grid would miss a constraining neighbour that lies
+    /// beyond its cell reach, letting the sphere grow straight through it.
+    fn grow_sphere(&mut self, idx: usize) {
+        let center = self.spheres[idx].center;
+        let neighbour_gap = self.spheres
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != idx)
+            .map(|(_, sphere)| self.container.distance(&center, &sphere.center) - sphere.radius)
+            .fold(::std::f32::INFINITY, f32::min);
+
+        // `neighbour_gap` is +INFINITY when `idx` has no other spheres at all; a
+        // binary search between `low` and `+INFINITY` never converges, since every
+        // midpoint is itself infinite. Clamp the upper bound to a finite, container-
+        // derived scale instead, so the search always closes in on the wall.
+        let container_scale = (3. * self.container.volume() / (4. * ::std::f32::consts::PI)).cbrt();
+        let mut low = self.spheres[idx].radius;
+        let mut high = neighbour_gap.min(container_scale).max(low);
+        for _ in 0..20 {
+            let mid = 0.5 * (low + high);
+            if self.container.contains(&Sphere::new(center, mid)) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        self.spheres[idx].radius = low;
+    }
+
+    /// Attempts a small translation of the sphere at `idx` toward the centroid of its
+    /// contacts, accepting the move only if it stays inside `container` and does not
+    /// overlap any other sphere.
+    ///
+    /// Rebuilds the neighbour grid immediately before checking for overlaps, so the
+    /// check sees the radius `grow_sphere` may have just assigned `idx` (and any other
+    /// sphere already grown or nudged earlier in the same sweep) rather than a grid
+    /// built at the start of the sweep.
+    fn nudge_toward_contacts(&mut self, idx: usize) {
+        let grid = self.neighbour_grid(DEFAULT_CONTACT_TOLERANCE);
+        let contacts = self.contact_indices(idx, &grid, DEFAULT_CONTACT_TOLERANCE);
+        if contacts.is_empty() {
+            return;
+        }
+
+        let center = self.spheres[idx].center;
+        let radius = self.spheres[idx].radius;
+        let sum = contacts.iter().fold(Vector3::new(0., 0., 0.), |acc, &c| {
+            acc + nearest_image(&self.container, &center, &self.spheres[c]).center.coords
+        });
+        let centroid = Point3::from_coordinates(sum / contacts.len() as f32);
+
+        let direction = centroid - center;
+        let magnitude = ops::norm(&direction);
+        if magnitude < ::std::f32::EPSILON {
+            return;
+        }
+        let step = 0.01 * radius;
+        let candidate_center = center + direction / magnitude * step;
+        let candidate = Sphere::new(candidate_center, radius);
+
+        let overlaps_any = grid
+            .neighbours(&candidate_center)
+            .into_iter()
+            .filter(|&other| other != idx)
+            .any(|other| {
+                candidate.overlaps(&nearest_image(&self.container, &candidate_center, &self.spheres[other]))
+            });
+
+        if self.container.contains(&candidate) && !overlaps_any {
+            self.spheres[idx].center = candidate_center;
+        }
     }
 }
 
@@ -216,16 +601,30 @@ impl<C: Container> PackedVolume<C> {
 pub fn pack_spheres<C: Container, R: IndependentSample<f64>>(
     container: &C,
     size_distribution: &mut R,
+) -> Vec<Sphere> {
+    let mut rng = rand::thread_rng();
+    pack_spheres_seeded(container, size_distribution, &mut rng)
+}
+
+/// As [pack_spheres](fn.pack_spheres.html), but threading a caller-supplied `rng`
+/// through every random decision in the algorithm (the initial triple of radii, the
+/// random front pick, and the random `set_f` selection), instead of reaching for
+/// `rand::thread_rng()` internally. Passing a seeded `rng` (e.g. `rand::StdRng`) makes
+/// the resulting packing deterministic for a fixed seed, which `pack_spheres` cannot
+/// offer since it always draws from the thread-local generator.
+pub fn pack_spheres_seeded<C: Container, R: IndependentSample<f64>, Rg: Rng>(
+    container: &C,
+    size_distribution: &mut R,
+    rng: &mut Rg,
 ) -> Vec<Sphere> {
     // IndependentSample is already derrived for all distributions in `rand` with f64,
     // so we just downsample here instead of implementing traits on f32 for everything.
-    let mut rng = rand::thread_rng();
 
     // Radii of three initial spheres, taken from the input distribution
     let init_radii: [f32; 3] = [
-        size_distribution.ind_sample(&mut rng) as f32,
-        size_distribution.ind_sample(&mut rng) as f32,
-        size_distribution.ind_sample(&mut rng) as f32,
+        size_distribution.ind_sample(rng) as f32,
+        size_distribution.ind_sample(rng) as f32,
+        size_distribution.ind_sample(rng) as f32,
     ];
 
     // S := {s₁, s₂, s₃}
@@ -235,18 +634,32 @@ pub fn pack_spheres<C: Container, R: IndependentSample<f64>>(
     let mut front = spheres.clone();
 
     // Radius of new sphere to be added to the current front, taken from the input distribution
-    let mut new_radius = size_distribution.ind_sample(&mut rng) as f32;
+    let mut new_radius = size_distribution.ind_sample(rng) as f32;
+
+    // Accelerates the V-set lookup below: cells are kept large enough that any sphere
+    // able to satisfy the `d(c₀, c') ≤ r₀ + r' + 2r` test lies in the query cell or one
+    // of its 26 neighbours, so we never have to scan the whole `spheres` vector.
+    let mut max_radius = init_radii.iter().cloned().fold(0f32, f32::max).max(
+        new_radius,
+    );
+    let mut cell_size = 2. * max_radius + 2. * new_radius;
+    let mut grid = build_grid(
+        container,
+        &spheres.iter().map(|sphere| sphere.center).collect::<Vec<_>>(),
+        cell_size,
+    );
 
     'outer: while !front.is_empty() {
         // s₀ := s(c₀, r₀) picked at random from F
         let curr_sphere = rng.choose(&front).unwrap().clone();
         // V := {s(c', r') ∈ S : d(c₀, c') ≤ r₀ + r' + 2r}
-        let set_v = spheres
-            .iter()
-            .cloned()
+        let set_v = grid
+            .neighbours(&curr_sphere.center)
+            .into_iter()
+            .map(|idx| nearest_image(container, &curr_sphere.center, &spheres[idx]))
             .filter(|s_dash| {
                 s_dash != &curr_sphere &&
-                    nalgebra::distance(&curr_sphere.center, &s_dash.center) <=
+                    container.distance(&curr_sphere.center, &s_dash.center) <=
                         curr_sphere.radius + s_dash.radius + 2. * new_radius
             })
             .collect::<Vec<_>>();
@@ -259,7 +672,22 @@ pub fn pack_spheres<C: Container, R: IndependentSample<f64>>(
                 let s_new = rng.choose(&set_f).unwrap();
                 front.push(s_new.clone());
                 spheres.push(s_new.clone());
-                new_radius = size_distribution.ind_sample(&mut rng) as f32;
+                let new_idx = spheres.len() - 1;
+                grid.insert(new_idx, &s_new.center);
+                max_radius = max_radius.max(s_new.radius);
+                new_radius = size_distribution.ind_sample(rng) as f32;
+                max_radius = max_radius.max(new_radius);
+                // Grow the grid if spheres seen so far could now interact beyond the
+                // current cell size; a rebuild is O(N) but happens rarely in practice.
+                let required_cell_size = 2. * max_radius + 2. * new_radius;
+                if required_cell_size > cell_size {
+                    cell_size = required_cell_size;
+                    grid = build_grid(
+                        container,
+                        &spheres.iter().map(|sphere| sphere.center).collect::<Vec<_>>(),
+                        cell_size,
+                    );
+                }
                 continue 'outer;
             }
         }
@@ -269,6 +697,201 @@ pub fn pack_spheres<C: Container, R: IndependentSample<f64>>(
     spheres
 }
 
+/// Packs instances of a rigid `template` [Clump](clump/struct.Clump.html) into
+/// `container`, generalising the sphere advancing front to a non-spherical grain
+/// shape.
+///
+/// Candidate positions are discovered exactly as in `pack_spheres`, using the
+/// template's [bounding_radius](clump/struct.Clump.html#method.bounding_radius) as a
+/// stand-in particle size (scaled by `size_distribution`, which should therefore
+/// produce values around `1.0`). At each candidate position, up to
+/// `orientation_attempts` random orientations of the whole clump are tried; a clump is
+/// accepted only once every constituent sphere satisfies `container.contains` and
+/// does not overlap any already-placed sphere (checked via the same neighbour grid
+/// used by `pack_spheres`).
+pub fn pack_clumps<C: Container, R: IndependentSample<f64>>(
+    container: &C,
+    size_distribution: &mut R,
+    template: &Clump,
+    orientation_attempts: usize,
+) -> Vec<Clump> {
+    let mut rng = rand::thread_rng();
+    pack_clumps_seeded(container, size_distribution, template, orientation_attempts, &mut rng)
+}
+
+/// As [pack_clumps](fn.pack_clumps.html), but threading a caller-supplied `rng` through
+/// every random decision (the underlying advancing front and the random orientation
+/// attempts), instead of reaching for `rand::thread_rng()` internally. Passing a seeded
+/// `rng` makes the resulting packing deterministic for a fixed seed, matching
+/// [pack_spheres_seeded](fn.pack_spheres_seeded.html).
+pub fn pack_clumps_seeded<C: Container, R: IndependentSample<f64>, Rg: Rng>(
+    container: &C,
+    size_distribution: &mut R,
+    template: &Clump,
+    orientation_attempts: usize,
+    rng: &mut Rg,
+) -> Vec<Clump> {
+    let bounding_radius = template.bounding_radius();
+
+    // Reuse the sphere advancing front to discover candidate clump centres: its
+    // output spheres are discarded, only their positions matter.
+    let mut scaled = ScaledSample {
+        inner: size_distribution,
+        scale: bounding_radius as f64,
+    };
+    let placeholders = pack_spheres_seeded(container, &mut scaled, rng);
+
+    let mut placed_spheres: Vec<Sphere> = Vec::new();
+    let mut grid = build_grid(container, &[], 2. * bounding_radius + 0.002);
+    let mut clumps = Vec::new();
+
+    for placeholder in placeholders {
+        // The candidate position is fixed across every orientation attempt below, so
+        // the set of already-placed spheres close enough to matter doesn't change
+        // either: look it up once rather than on every attempt.
+        let nearby: Vec<Sphere> = grid
+            .neighbours(&placeholder.center)
+            .into_iter()
+            .map(|idx| nearest_image(container, &placeholder.center, &placed_spheres[idx]))
+            .collect();
+
+        let mut accepted = None;
+        for _ in 0..orientation_attempts {
+            let axis = random_unit_vector(rng);
+            let angle = rng.gen_range(0., 2. * ::std::f32::consts::PI);
+            let orientation = Rotation3::from_axis_angle(&axis, angle);
+            let candidate = template.placed_at(placeholder.center, orientation);
+
+            if candidate.contains_in(container) && !candidate.overlaps_any(&nearby) {
+                accepted = Some(candidate);
+                break;
+            }
+        }
+
+        if let Some(candidate) = accepted {
+            for sphere in candidate.spheres() {
+                let idx = placed_spheres.len();
+                grid.insert(idx, &sphere.center);
+                placed_spheres.push(sphere);
+            }
+            clumps.push(candidate);
+        }
+    }
+
+    clumps
+}
+
+/// Adapts a radius distribution into one producing values scaled by a clump's
+/// bounding radius, so `pack_spheres_seeded` can be reused to search for clump
+/// candidate positions while `size_distribution` itself only controls relative scale
+/// (e.g. values clustered around `1.0`).
+struct ScaledSample<'a, R: 'a> {
+    inner: &'a mut R,
+    scale: f64,
+}
+
+impl<'a, R: IndependentSample<f64>> Sample<f64> for ScaledSample<'a, R> {
+    fn sample<Rg: Rng>(&mut self, rng: &mut Rg) -> f64 {
+        self.inner.ind_sample(rng) * self.scale
+    }
+}
+
+impl<'a, R: IndependentSample<f64>> IndependentSample<f64> for ScaledSample<'a, R> {
+    fn ind_sample<Rg: Rng>(&self, rng: &mut Rg) -> f64 {
+        self.inner.ind_sample(rng) * self.scale
+    }
+}
+
+/// To obtain quantitative values of a clump packing's effectiveness, `PackedClumpVolume`
+/// mirrors [PackedVolume](struct.PackedVolume.html), but sums clump volumes (which
+/// already account for intra-clump sphere overlaps) rather than plain sphere volumes.
+#[derive(Debug)]
+pub struct PackedClumpVolume<C> {
+    /// A set of clumps generated by a call to [pack_clumps](fn.pack_clumps.html).
+    pub clumps: Vec<Clump>,
+    /// The container in which clumps have been packed.
+    pub container: C,
+}
+
+impl<C: Container> PackedClumpVolume<C> {
+    /// Creates a new `PackedClumpVolume` by calling [pack_clumps](fn.pack_clumps.html)
+    /// with a given `template` clump, scale distribution, and `container` to pack into.
+    pub fn new<R: IndependentSample<f64>>(
+        container: C,
+        size_distribution: &mut R,
+        template: &Clump,
+        orientation_attempts: usize,
+    ) -> PackedClumpVolume<C> {
+        PackedClumpVolume::<C> {
+            clumps: pack_clumps(&container, size_distribution, template, orientation_attempts),
+            container: container,
+        }
+    }
+
+    /// As [new](#method.new), but threading a caller-supplied `rng` through every
+    /// random decision via [pack_clumps_seeded](fn.pack_clumps_seeded.html), instead of
+    /// reaching for `rand::thread_rng()` internally.
+    pub fn with_rng<R: IndependentSample<f64>, Rg: Rng>(
+        container: C,
+        size_distribution: &mut R,
+        template: &Clump,
+        orientation_attempts: usize,
+        rng: &mut Rg,
+    ) -> PackedClumpVolume<C> {
+        PackedClumpVolume::<C> {
+            clumps: pack_clumps_seeded(
+                &container,
+                size_distribution,
+                template,
+                orientation_attempts,
+                rng,
+            ),
+            container: container,
+        }
+    }
+
+    /// Calculates the volume fraction ν = Vs/V: the combined volume of all packed
+    /// clumps (each already correcting for intra-clump sphere overlaps) divided by the
+    /// volume of the container.
+    ///
+    /// Any clump of three or more spheres falls back to a Monte-Carlo volume estimate
+    /// drawn from `rand::thread_rng()`; see
+    /// [volume_fraction_with_rng](#method.volume_fraction_with_rng) for a reproducible
+    /// variant.
+    pub fn volume_fraction(&self) -> f32 {
+        let mut rng = rand::thread_rng();
+        self.volume_fraction_with_rng(&mut rng)
+    }
+
+    /// As [volume_fraction](#method.volume_fraction), but threading a caller-supplied
+    /// `rng` through each clump's [volume_with_rng](clump/struct.Clump.html#method.volume_with_rng)
+    /// instead of reaching for `rand::thread_rng()` internally.
+    pub fn volume_fraction_with_rng<Rg: Rng>(&self, rng: &mut Rg) -> f32 {
+        let vol_clumps: f32 = self.clumps.iter().map(|clump| clump.volume_with_rng(rng)).sum();
+        vol_clumps / self.container.volume()
+    }
+
+    /// Calculates the void ratio e = Vv/Vs: the volume of all void space divided by
+    /// the volume of solids (packed clumps) in the container.
+    ///
+    /// As with [volume_fraction](#method.volume_fraction), clumps of three or more
+    /// spheres draw from `rand::thread_rng()`; see
+    /// [void_ratio_with_rng](#method.void_ratio_with_rng) for a reproducible variant.
+    pub fn void_ratio(&self) -> f32 {
+        let mut rng = rand::thread_rng();
+        self.void_ratio_with_rng(&mut rng)
+    }
+
+    /// As [void_ratio](#method.void_ratio), but threading a caller-supplied `rng`
+    /// through each clump's [volume_with_rng](clump/struct.Clump.html#method.volume_with_rng)
+    /// instead of reaching for `rand::thread_rng()` internally.
+    pub fn void_ratio_with_rng<Rg: Rng>(&self, rng: &mut Rg) -> f32 {
+        let vol_clumps: f32 = self.clumps.iter().map(|clump| clump.volume_with_rng(rng)).sum();
+        let vol_total = self.container.volume();
+        (vol_total - vol_clumps) / vol_clumps
+    }
+}
+
 /// Creates three initial spheres that are tangent pairwise. The incenter of the triangle formed
 /// by verticies located at the centers of each sphere is aligned at the origin.
 fn init_spheres<C: Container>(radii: &[f32; 3], container: &C) -> Vec<Sphere> {
@@ -292,8 +915,9 @@ fn init_spheres<C: Container>(radii: &[f32; 3], container: &C) -> Vec<Sphere> {
     let distance_b = radius_a + radius_c;
     let distance_a = radius_c + radius_b;
 
-    let x = (distance_b.powi(2) + distance_c.powi(2) - distance_a.powi(2)) / (2. * distance_c);
-    let y = (distance_b.powi(2) - x.powi(2)).sqrt();
+    let x = (ops::powi(distance_b, 2) + ops::powi(distance_c, 2) - ops::powi(distance_a, 2)) /
+        (2. * distance_c);
+    let y = ops::sqrt(ops::powi(distance_b, 2) - ops::powi(x, 2));
 
     // Find incenter
     let perimeter = distance_a + distance_b + distance_c;
@@ -359,36 +983,38 @@ fn identify_f<C: Container>(
     let distance_34 = s_3.radius + radius;
 
     let vector_u = s_1.center - s_2.center;
-    let unitvector_u = vector_u / nalgebra::norm(&vector_u);
+    let unitvector_u = vector_u / ops::norm(&vector_u);
     let vector_v = s_1.center - s_3.center;
-    let unitvector_v = vector_v / nalgebra::norm(&vector_v);
+    let unitvector_v = vector_v / ops::norm(&vector_v);
     let cross_uv = Matrix::cross(&vector_u, &vector_v);
-    let unitvector_t = cross_uv / nalgebra::norm(&cross_uv);
+    let unitvector_t = cross_uv / ops::norm(&cross_uv);
     let vector_w = -2. * s_1.center.coords;
 
-    let distance_a = (distance_24.powi(2) - distance_14.powi(2) + s_1.center.x.powi(2) +
-                          s_1.center.y.powi(2) + s_1.center.z.powi(2) -
-                          s_2.center.x.powi(2) -
-                          s_2.center.y.powi(2) - s_2.center.z.powi(2)) /
-        (2. * nalgebra::norm(&vector_u));
-    let distance_b = (distance_34.powi(2) - distance_14.powi(2) + s_1.center.x.powi(2) +
-                          s_1.center.y.powi(2) + s_1.center.z.powi(2) -
-                          s_3.center.x.powi(2) -
-                          s_3.center.y.powi(2) - s_3.center.z.powi(2)) /
-        (2. * nalgebra::norm(&vector_v));
-    let distance_c = distance_14.powi(2) - s_1.center.x.powi(2) - s_1.center.y.powi(2) -
-        s_1.center.z.powi(2);
+    let distance_a = (ops::powi(distance_24, 2) - ops::powi(distance_14, 2) +
+                          ops::powi(s_1.center.x, 2) + ops::powi(s_1.center.y, 2) +
+                          ops::powi(s_1.center.z, 2) -
+                          ops::powi(s_2.center.x, 2) -
+                          ops::powi(s_2.center.y, 2) - ops::powi(s_2.center.z, 2)) /
+        (2. * ops::norm(&vector_u));
+    let distance_b = (ops::powi(distance_34, 2) - ops::powi(distance_14, 2) +
+                          ops::powi(s_1.center.x, 2) + ops::powi(s_1.center.y, 2) +
+                          ops::powi(s_1.center.z, 2) -
+                          ops::powi(s_3.center.x, 2) -
+                          ops::powi(s_3.center.y, 2) - ops::powi(s_3.center.z, 2)) /
+        (2. * ops::norm(&vector_v));
+    let distance_c = ops::powi(distance_14, 2) - ops::powi(s_1.center.x, 2) -
+        ops::powi(s_1.center.y, 2) - ops::powi(s_1.center.z, 2);
 
     let dot_uv = nalgebra::dot(&unitvector_u, &unitvector_v);
     let dot_wt = nalgebra::dot(&vector_w, &unitvector_t);
     let dot_uw = nalgebra::dot(&unitvector_u, &vector_w);
     let dot_vw = nalgebra::dot(&unitvector_v, &vector_w);
 
-    let alpha = (distance_a - distance_b * dot_uv) / (1. - dot_uv.powi(2));
-    let beta = (distance_b - distance_a * dot_uv) / (1. - dot_uv.powi(2));
-    let value_d = alpha.powi(2) + beta.powi(2) + 2. * alpha * beta * dot_uv + alpha * dot_uw +
-        beta * dot_vw - distance_c;
-    let dot_wt_2 = dot_wt.powi(2);
+    let alpha = (distance_a - distance_b * dot_uv) / (1. - ops::powi(dot_uv, 2));
+    let beta = (distance_b - distance_a * dot_uv) / (1. - ops::powi(dot_uv, 2));
+    let value_d = ops::powi(alpha, 2) + ops::powi(beta, 2) + 2. * alpha * beta * dot_uv +
+        alpha * dot_uw + beta * dot_vw - distance_c;
+    let dot_wt_2 = ops::powi(dot_wt, 2);
     let value_4d = 4. * value_d;
 
     let mut f = Vec::new();
@@ -396,8 +1022,8 @@ fn identify_f<C: Container>(
     // so we must check this comparison. TODO: Would be nice to have
     // some quick way of verifying this configuration and deny it early.
     if dot_wt_2 > value_4d {
-        let gamma_pos = 0.5 * (-dot_wt + (dot_wt.powi(2) - 4. * value_d).sqrt());
-        let gamma_neg = 0.5 * (-dot_wt - (dot_wt.powi(2) - 4. * value_d).sqrt());
+        let gamma_pos = 0.5 * (-dot_wt + ops::sqrt(ops::powi(dot_wt, 2) - 4. * value_d));
+        let gamma_neg = 0.5 * (-dot_wt - ops::sqrt(ops::powi(dot_wt, 2) - 4. * value_d));
 
         let s_4_positive = Sphere::new(
             Point3::from_coordinates(
@@ -423,6 +1049,59 @@ fn identify_f<C: Container>(
     f
 }
 
+/// Draws a point uniformly at random from the spherical shell `[r_inner, r_outer)`
+/// centred on `center`, used to Monte-Carlo sample how much of that shell lies inside
+/// a container.
+fn random_point_in_shell<R: Rng>(
+    center: &Point3<f32>,
+    r_inner: f32,
+    r_outer: f32,
+    rng: &mut R,
+) -> Point3<f32> {
+    // Sampling r uniformly in r³ (rather than r itself) keeps the points uniformly
+    // distributed by volume within the shell.
+    let r = rng.gen_range(r_inner.powi(3), r_outer.powi(3)).cbrt();
+    let costheta: f32 = rng.gen_range(-1., 1.);
+    let theta = costheta.acos();
+    let phi: f32 = rng.gen_range(0., 2. * ::std::f32::consts::PI);
+
+    Point3::new(
+        center.x + r * theta.sin() * phi.cos(),
+        center.y + r * theta.sin() * phi.sin(),
+        center.z + r * theta.cos(),
+    )
+}
+
+/// Builds a `SpatialGrid` over `centers`, sized by `cell_size`, that wraps around
+/// `container`'s periodic box (see [Container::period](trait.Container.html#method.period))
+/// if it has one. Every grid built against a generic `C: Container` should go through
+/// this helper rather than calling `SpatialGrid::build` directly, so a periodic
+/// container's minimum-image `distance` and the grid's neighbour search stay
+/// consistent with one another.
+fn build_grid<C: Container>(container: &C, centers: &[Point3<f32>], cell_size: f32) -> SpatialGrid {
+    match container.period() {
+        Some(period) => SpatialGrid::build_periodic(centers, cell_size, period),
+        None => SpatialGrid::build(centers, cell_size),
+    }
+}
+
+/// Returns a copy of `sphere` moved to its periodic image closest to `origin` when
+/// `container` has a periodic box, or unchanged otherwise, so Euclidean tests such as
+/// `Sphere::overlaps` and the tangent-sphere solver see it at the minimum-image
+/// distance that `container.distance` reports.
+fn nearest_image<C: Container>(container: &C, origin: &Point3<f32>, sphere: &Sphere) -> Sphere {
+    let mut image = sphere.clone();
+    if let Some(period) = container.period() {
+        let delta = sphere.center - *origin;
+        image.center = Point3::new(
+            origin.x + delta.x - period * (delta.x / period).round(),
+            origin.y + delta.y - period * (delta.y / period).round(),
+            origin.z + delta.z - period * (delta.z / period).round(),
+        );
+    }
+    image
+}
+
 /// Calculates all possible pairs of a `set` of values.
 fn pairs(set: &[Sphere]) -> Vec<(&Sphere, &Sphere)> {
     let n = set.len();
@@ -0,0 +1,44 @@
+//! Fixed-precision math primitives used by the tangent-sphere solver.
+//!
+//! `f32::sqrt` has unspecified precision beyond IEEE-754 correct rounding of the
+//! underlying operation, so the exact tangent-sphere solution chosen by
+//! [identify_f](../fn.identify_f.html) can drift between machines even for identical
+//! inputs, breaking bit-reproducible packings. Building with the `libm` feature routes
+//! `sqrt` (and, transitively, `norm`) through the pure-Rust
+//! [libm](https://crates.io/crates/libm) crate instead of the platform's native
+//! implementation, trading a little speed for a deterministic result across platforms.
+//! `powi` is exact integer-power multiplication regardless of this feature: it is
+//! already deterministic, and `libm`'s `powf` would be both slower and a precision/sign
+//! regression for it (see its doc comment below).
+
+use nalgebra::Vector3;
+
+/// Square root of `x`.
+#[cfg(feature = "libm")]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    ::libm::sqrtf(x)
+}
+
+/// Square root of `x`.
+#[cfg(not(feature = "libm"))]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    x.sqrt()
+}
+
+/// Raises `x` to the integer power `n`.
+///
+/// Unlike `sqrt`, this is not routed through `libm` even under the `libm` feature:
+/// `libm::powf` is a transcendental exp/log implementation, which is both slower and
+/// less precise than repeated exact multiplication for integer exponents, and its
+/// sign handling for negative bases (routine here, since sphere-centre coordinates are
+/// squared/cubed throughout the tangent-sphere solver) depends on `powf`'s own
+/// integer-exponent special case rather than being guaranteed. `f32::powi` is exact
+/// multiplication on every platform, so it is already deterministic.
+pub(crate) fn powi(x: f32, n: i32) -> f32 {
+    x.powi(n)
+}
+
+/// Euclidean norm (magnitude) of a vector.
+pub(crate) fn norm(v: &Vector3<f32>) -> f32 {
+    sqrt(v.x * v.x + v.y * v.y + v.z * v.z)
+}
@@ -0,0 +1,59 @@
+//! Periodic (toroidal) containers, used to generate bulk packings free of the
+//! wall-depletion artefacts that bias [volume_fraction](../struct.PackedVolume.html#method.volume_fraction)
+//! in finite spherical or cuboid containers.
+
+use Container;
+use nalgebra::Point3;
+use shapes::Sphere;
+
+/// A rectangular box with periodic boundary conditions on all three axes, as used for
+/// random sequential and close-random packing studies.
+///
+/// Unlike [Cuboid](../shapes/struct.Cuboid.html), `PeriodicCuboid` never rejects a
+/// sphere for crossing a face: a sphere that pokes out one side is understood to
+/// continue from the opposite side, so [contains](#method.contains) always returns
+/// `true` and [distance](#method.distance) uses the minimum-image convention instead
+/// of ordinary Euclidean distance.
+#[derive(Debug, Clone, Copy)]
+pub struct PeriodicCuboid {
+    /// Edge length of the box along each axis.
+    pub side: f32,
+}
+
+impl PeriodicCuboid {
+    /// Creates a new cubic periodic container with edge length `side`.
+    pub fn new(side: f32) -> PeriodicCuboid {
+        PeriodicCuboid { side: side }
+    }
+
+    /// Wraps a single coordinate difference into `[-side/2, side/2)`, the minimum-image
+    /// convention: the shortest displacement between two points once periodic images
+    /// are taken into account.
+    fn wrap(&self, delta: f32) -> f32 {
+        let wrapped = delta - self.side * (delta / self.side + 0.5).floor();
+        wrapped
+    }
+}
+
+impl Container for PeriodicCuboid {
+    fn contains(&self, _sphere: &Sphere) -> bool {
+        // A periodic box has no walls: every sphere is considered inside, since a
+        // sphere crossing a face simply continues from its periodic image.
+        true
+    }
+
+    fn volume(&self) -> f32 {
+        self.side.powi(3)
+    }
+
+    fn distance(&self, a: &Point3<f32>, b: &Point3<f32>) -> f32 {
+        let dx = self.wrap(a.x - b.x);
+        let dy = self.wrap(a.y - b.y);
+        let dz = self.wrap(a.z - b.z);
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    fn period(&self) -> Option<f32> {
+        Some(self.side)
+    }
+}